@@ -0,0 +1,121 @@
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use futures_util::StreamExt;
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::{header, RequestBuilder, StatusCode, Url};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+/// last non-empty path segment of `url`, falling back to `index.html`
+fn filename_from_url(url: &Url) -> PathBuf {
+    let name = url.path_segments()
+        .and_then(|mut segs| segs.rfind(|s| !s.is_empty()))
+        .filter(|s| !s.is_empty())
+        .unwrap_or("index.html");
+    PathBuf::from(name)
+}
+
+/// the filename suggested by a `Content-Disposition: attachment; filename=...` header
+fn filename_from_content_disposition(headers: &header::HeaderMap) -> Option<PathBuf> {
+    let value = headers.get(header::CONTENT_DISPOSITION)?.to_str().ok()?;
+    let name = value.split("filename=").nth(1)?.trim_matches('"');
+    if name.is_empty() { None } else { Some(PathBuf::from(name)) }
+}
+
+/// stream `builder`'s response to disk, driving an indicatif progress bar
+/// off `Content-Length`. If `explicit_path` is `None`, the filename is
+/// derived from the URL (or, for a fresh download, `Content-Disposition`).
+/// If the target file already exists, resume it with a `Range` request.
+/// Returns the saved path along with any cookies the response set, so the
+/// caller can persist them into a `--session` the same way a non-download
+/// response would.
+pub async fn download(builder: RequestBuilder, url: &Url, explicit_path: Option<&Path>) -> Result<(PathBuf, Vec<(String, String)>)> {
+    let guessed_path = explicit_path.map(Path::to_path_buf).unwrap_or_else(|| filename_from_url(url));
+    let existing_len = tokio::fs::metadata(&guessed_path).await.map(|m| m.len()).unwrap_or(0);
+
+    let builder = if existing_len > 0 {
+        builder.header(header::RANGE, format!("bytes={}-", existing_len))
+    } else {
+        builder
+    };
+
+    let response = builder.send().await.map_err(|e| {
+        if e.is_redirect() {
+            anyhow::anyhow!("too many redirects: {}", e)
+        } else {
+            anyhow::anyhow!(e)
+        }
+    })?;
+    let resuming = existing_len > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let cookies = response.cookies().map(|c| (c.name().to_string(), c.value().to_string())).collect::<Vec<_>>();
+
+    let path = match explicit_path {
+        Some(p) => p.to_path_buf(),
+        None if !resuming => filename_from_content_disposition(response.headers()).unwrap_or(guessed_path),
+        None => guessed_path,
+    };
+
+    let remaining = response.content_length().unwrap_or(0);
+    let total = if resuming { existing_len + remaining } else { remaining };
+
+    let bar = ProgressBar::new(total);
+    if let Ok(style) = ProgressStyle::default_bar()
+        .template("{bar:40.cyan/blue} {bytes}/{total_bytes} ({eta})")
+    {
+        bar.set_style(style);
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resuming)
+        .truncate(!resuming)
+        .open(&path)
+        .await?;
+    if resuming {
+        file.seek(SeekFrom::End(0)).await?;
+    }
+
+    let mut written = if resuming { existing_len } else { 0 };
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+        bar.set_position(written);
+    }
+    bar.finish();
+
+    Ok((path, cookies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_from_url_uses_last_path_segment() {
+        let url = Url::parse("https://example.com/files/report.pdf").unwrap();
+        assert_eq!(filename_from_url(&url), PathBuf::from("report.pdf"));
+    }
+
+    #[test]
+    fn filename_from_url_falls_back_to_index_html() {
+        let url = Url::parse("https://example.com/").unwrap();
+        assert_eq!(filename_from_url(&url), PathBuf::from("index.html"));
+    }
+
+    #[test]
+    fn filename_from_content_disposition_extracts_quoted_name() {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(header::CONTENT_DISPOSITION, "attachment; filename=\"report.pdf\"".parse().unwrap());
+        assert_eq!(filename_from_content_disposition(&headers), Some(PathBuf::from("report.pdf")));
+    }
+
+    #[test]
+    fn filename_from_content_disposition_is_none_without_header() {
+        let headers = header::HeaderMap::new();
+        assert_eq!(filename_from_content_disposition(&headers), None);
+    }
+}
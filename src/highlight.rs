@@ -0,0 +1,75 @@
+use mime::Mime;
+use once_cell::sync::Lazy;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// map a response content-type to the syntax used to highlight its body
+fn syntax_for(mime: Option<&Mime>) -> &'static SyntaxReference {
+    let name = match mime {
+        Some(m) if *m == mime::APPLICATION_JSON => "JSON",
+        Some(m) if *m == mime::TEXT_HTML => "HTML",
+        Some(m) if m.subtype() == "xml" => "XML",
+        Some(m) if *m == mime::TEXT_CSS => "CSS",
+        Some(m) if m.subtype() == "javascript" => "JavaScript",
+        _ => "Plain Text",
+    };
+    SYNTAX_SET.find_syntax_by_name(name).unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text())
+}
+
+fn theme_by_name(name: &str) -> &'static Theme {
+    THEME_SET.themes.get(name).unwrap_or_else(|| &THEME_SET.themes["base16-ocean.dark"])
+}
+
+/// pretty-print (JSON only) and syntax-highlight a response body for the
+/// given content type; when `color` is false (stdout isn't a tty, or
+/// `--no-color` was passed) the body is returned without escape codes,
+/// still pretty-printed if it is JSON
+pub fn highlight(body: &str, mime: Option<&Mime>, theme: &str, color: bool) -> String {
+    let pretty = match mime {
+        Some(m) if *m == mime::APPLICATION_JSON => {
+            jsonxf::pretty_print(body).unwrap_or_else(|_| body.to_string())
+        },
+        _ => body.to_string(),
+    };
+
+    if !color {
+        return pretty;
+    }
+
+    let syntax = syntax_for(mime);
+    let mut highlighter = HighlightLines::new(syntax, theme_by_name(theme));
+
+    let mut out = String::new();
+    for line in LinesWithEndings::from(&pretty) {
+        if let Ok(ranges) = highlighter.highlight_line(line, &SYNTAX_SET) {
+            out.push_str(&as_24_bit_terminal_escaped(&ranges, false));
+        }
+    }
+    out.push_str("\x1b[0m");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn syntax_for_maps_known_mime_types() {
+        assert_eq!(syntax_for(Some(&mime::APPLICATION_JSON)).name, "JSON");
+        assert_eq!(syntax_for(Some(&mime::TEXT_HTML)).name, "HTML");
+        assert_eq!(syntax_for(Some(&mime::TEXT_CSS)).name, "CSS");
+        assert_eq!(syntax_for(None).name, "Plain Text");
+    }
+
+    #[test]
+    fn theme_by_name_falls_back_to_base16_ocean_dark() {
+        let fallback = theme_by_name("not-a-real-theme");
+        let expected = theme_by_name("base16-ocean.dark");
+        assert_eq!(fallback.name, expected.name);
+    }
+}
@@ -1,67 +1,176 @@
-use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
 use std::str::FromStr;
 use clap::{Parser, Subcommand};
 use anyhow::{anyhow, Result};
-use reqwest::{Client, header, Response, Url};
+use reqwest::{Client, header, Method, Response, Url};
 use colored::Colorize;
 use mime::Mime;
+use serde_json::Value;
 
-/// http GET request
-#[derive(Parser, Debug)]
-struct Get {
-    /// http get request url
-    #[clap(parse(try_from_str = parse_url))]
-    url: String,
+mod download;
+mod highlight;
+mod session;
+
+/// a request item: `key=value` (json string field), `key:=value` (raw json
+/// value), `key==value` (url query param), `name:value` (header, with
+/// `name:` unsetting a default header) or `field@path` (file upload, only
+/// meaningful with `--multipart`)
+#[derive(Debug, PartialEq, Clone)]
+enum RequestItem {
+    JsonField(String, Value),
+    RawJsonField(String, Value),
+    QueryParam(String, String),
+    Header(String, Option<String>),
+    FileField(String, PathBuf),
+}
+
+/// which separator a request item used
+enum Separator {
+    Query,
+    RawJson,
+    Json,
+    Header,
+    File,
+}
+
+impl FromStr for RequestItem {
+    type Err = anyhow::Error;
+
+    /// see https://httpie.io/docs/cli/request-items for the grammar this follows
+    ///
+    /// separators are picked by leftmost occurrence (ties broken toward the
+    /// longer, two-character separator), not fixed precedence — otherwise a
+    /// `field@C:\path` file item would be misread as a header just because
+    /// `:` was checked before `@`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let err = || anyhow!("Failed to parse request item: {}", s);
+
+        let candidates = [
+            (s.find("=="), 2, Separator::Query),
+            (s.find(":="), 2, Separator::RawJson),
+            (s.find('='), 1, Separator::Json),
+            (s.find(':'), 1, Separator::Header),
+            (s.find('@'), 1, Separator::File),
+        ];
+
+        let mut best: Option<(usize, usize, &Separator)> = None;
+        for (idx, len, sep) in &candidates {
+            if let Some(idx) = idx {
+                let is_better = match best {
+                    None => true,
+                    Some((best_idx, best_len, _)) => *idx < best_idx || (*idx == best_idx && *len > best_len),
+                };
+                if is_better {
+                    best = Some((*idx, *len, sep));
+                }
+            }
+        }
+
+        let (idx, len, sep) = best.ok_or_else(err)?;
+        let (k, v) = (&s[..idx], &s[idx + len..]);
+
+        Ok(match sep {
+            Separator::Query => RequestItem::QueryParam(k.to_string(), v.to_string()),
+            Separator::RawJson => {
+                let value: Value = serde_json::from_str(v).map_err(|_| err())?;
+                RequestItem::RawJsonField(k.to_string(), value)
+            },
+            Separator::Json => RequestItem::JsonField(k.to_string(), Value::String(v.to_string())),
+            Separator::Header => RequestItem::Header(
+                k.to_string(),
+                if v.is_empty() { None } else { Some(v.to_string()) },
+            ),
+            Separator::File => RequestItem::FileField(k.to_string(), PathBuf::from(v)),
+        })
+    }
+}
+
+fn parse_request_item(s: &str) -> Result<RequestItem> {
+    Ok(s.parse()?)
 }
 
 /// check valid url for get request
 fn parse_url(s: &str) -> Result<String> {
-    // let _url: Url = s.parse()?;  todo for 1: why can it work which written here
     let _url: Url = Url::parse(s)?;
     Ok(s.into())
 }
 
-/// http POST request
+/// a single http request: method comes from the subcommand, the rest of the
+/// shape (query, body, headers) comes from `items`
 #[derive(Parser, Debug)]
-struct Post {
-    /// http post request url
+struct Request {
+    /// http request url
     #[clap(parse(try_from_str = parse_url))]
     url: String,
 
-    /// http post request body
-    #[clap(parse(try_from_str = parse_kv_pair))]
-    body: Vec<KvPair>,
-}
+    /// request items: `k=v` json field, `k:=v` raw json, `k==v` query param, `k:v` header, `k@path` file
+    #[clap(parse(try_from_str = parse_request_item))]
+    items: Vec<RequestItem>,
 
-#[derive(Debug, PartialEq)]
-struct KvPair {
-    k: String,
-    v: String,
-}
+    /// send the body as application/x-www-form-urlencoded instead of json
+    #[clap(long, conflicts_with = "multipart")]
+    form: bool,
 
-impl FromStr for KvPair {
-    type Err = anyhow::Error;
+    /// send the body as multipart/form-data; `field@path` items stream a file
+    #[clap(long)]
+    multipart: bool,
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let err = || anyhow!("Failed to parse {}", s);
+    /// stream the response to this file instead of printing it
+    #[clap(short, long)]
+    output: Option<PathBuf>,
 
-        let mut it = s.split("=");
-        Ok(Self {
-            k: (it.next().ok_or_else(err)?).to_string(),
-            v: (it.next().ok_or_else(err)?).to_string(),
-        })
-    }
-}
+    /// like --output, but derive the filename from the url or Content-Disposition
+    #[clap(short, long)]
+    download: bool,
+
+    /// read the request body verbatim from stdin instead of from request items
+    #[clap(long)]
+    raw: bool,
+
+    /// which parts to print: any of request Headers (H), request Body (B),
+    /// response headers (h), response body (b)
+    #[clap(long, default_value = "hb")]
+    print: String,
 
-fn parse_kv_pair(s: &str) -> Result<KvPair> {
-    Ok(s.parse()?)  // todo for 1: why can it work which written here
+    /// print the fully-built request without sending it
+    #[clap(long)]
+    offline: bool,
 }
 
 /// see https://github.com/clap-rs/clap/blob/v3.1.1/examples/tutorial_derive/README.md
 #[derive(Subcommand, Debug)]
 enum SubCommand {
-    Get(Get),
-    Post(Post),
+    Get(Request),
+    Post(Request),
+    Put(Request),
+    Delete(Request),
+    Patch(Request),
+    Head(Request),
+}
+
+impl SubCommand {
+    fn method(&self) -> Method {
+        match self {
+            SubCommand::Get(_) => Method::GET,
+            SubCommand::Post(_) => Method::POST,
+            SubCommand::Put(_) => Method::PUT,
+            SubCommand::Delete(_) => Method::DELETE,
+            SubCommand::Patch(_) => Method::PATCH,
+            SubCommand::Head(_) => Method::HEAD,
+        }
+    }
+
+    fn args(&self) -> &Request {
+        match self {
+            SubCommand::Get(args)
+            | SubCommand::Post(args)
+            | SubCommand::Put(args)
+            | SubCommand::Delete(args)
+            | SubCommand::Patch(args)
+            | SubCommand::Head(args) => args,
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -69,6 +178,48 @@ enum SubCommand {
 struct Opts {
     #[clap(subcommand)]
     sub_cmd: SubCommand,
+
+    /// syntect theme used to highlight the response body
+    #[clap(long, default_value = "base16-ocean.dark")]
+    theme: String,
+
+    /// disable colored/highlighted output, even on a tty
+    #[clap(long)]
+    no_color: bool,
+
+    /// persist headers, auth and cookies across invocations under this name
+    #[clap(long)]
+    session: Option<String>,
+
+    /// HTTP Basic auth, as `user:pass`
+    #[clap(short, long, parse(try_from_str = parse_auth))]
+    auth: Option<(String, String)>,
+
+    /// Authorization: Bearer <token>
+    #[clap(long)]
+    bearer: Option<String>,
+
+    /// proxy all requests through this url
+    #[clap(long)]
+    proxy: Option<String>,
+
+    /// `no` skips TLS certificate verification (for self-signed certs)
+    #[clap(long, default_value = "yes")]
+    verify: String,
+
+    /// request timeout, in seconds
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// maximum number of redirects to follow before giving up
+    #[clap(long)]
+    max_redirects: Option<usize>,
+}
+
+/// parse `user:pass` for `-a/--auth`
+fn parse_auth(s: &str) -> Result<(String, String)> {
+    let (user, pass) = s.split_once(':').ok_or_else(|| anyhow!("auth must be user:pass, got {}", s))?;
+    Ok((user.to_string(), pass.to_string()))
 }
 
 fn print_status(response: &Response) {
@@ -83,13 +234,8 @@ fn print_header(response: &Response) {
     print!("\n");
 }
 
-fn print_body(m: Option<Mime>, body: &String) {
-    match m {
-        Some(v) if v == mime::APPLICATION_JSON => {
-            println!("{}", jsonxf::pretty_print(body).unwrap().cyan());
-        },
-        _ => println!("{}", body),
-    };
+fn print_body(m: Option<Mime>, body: &str, theme: &str, color: bool) {
+    println!("{}", highlight::highlight(body, m.as_ref(), theme, color));
 }
 
 fn get_content_type(response: &Response) -> Option<Mime> {
@@ -98,29 +244,259 @@ fn get_content_type(response: &Response) -> Option<Mime> {
         .map(|v| v.to_str().unwrap().parse().unwrap())
 }
 
-async fn print_response(response: Response) -> Result<()> {
-    print_status(&response);
-    print_header(&response);
+async fn print_response(response: Response, theme: &str, color: bool, show_headers: bool, show_body: bool) -> Result<()> {
+    if show_headers {
+        print_status(&response);
+        print_header(&response);
+    }
     let mime = get_content_type(&response);
     let body = response.text().await?;
-    print_body(mime, &body);
+    if show_body {
+        print_body(mime, &body, theme, color);
+    }
     Ok(())
 }
 
-async fn get(client: Client, args: &Get) -> Result<()> {
-    let response = client.get(&args.url).send().await?; // todo for 2: why not really args, but it is &args
-    // println!("{:?}", response.text().await?);
-    Ok(print_response(response).await?)
+/// print a fully-built, not-yet-sent request; used by `--offline` and the
+/// `H`/`B` (request headers/body) `--print` selectors
+fn print_request(req: &reqwest::Request, show_headers: bool, show_body: bool) {
+    if show_headers {
+        let status = format!("{} {}", req.method(), req.url()).blue();
+        println!("{}", status);
+        for (name, value) in req.headers() {
+            println!("{}: {:?}", name.to_string().green(), value);
+        }
+        println!();
+    }
+    if show_body {
+        if let Some(bytes) = req.body().and_then(|b| b.as_bytes()) {
+            println!("{}\n", String::from_utf8_lossy(bytes));
+        }
+    }
 }
 
-async fn post(client: Client, args: &Post) -> Result<()> {
-    let mut body = HashMap::new();
-    for pair in args.body.iter() {
-        body.insert(&pair.k, &pair.v);
-    };
-    let response = client.post(&args.url).json(&body).send().await?;
-    // println!("{:?}", response.text().await?);
-    Ok(print_response(response).await?)
+/// the fields, files, query params and header overrides gathered from a
+/// request's items (`None` header value means "unset this header")
+struct SplitItems {
+    fields: serde_json::Map<String, Value>,
+    files: Vec<(String, PathBuf)>,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, Option<String>)>,
+}
+
+fn split_items(items: &[RequestItem]) -> SplitItems {
+    let mut fields = serde_json::Map::new();
+    let mut files = Vec::new();
+    let mut query = Vec::new();
+    let mut headers = Vec::new();
+
+    for item in items {
+        match item {
+            RequestItem::JsonField(k, v) => { fields.insert(k.clone(), v.clone()); },
+            RequestItem::RawJsonField(k, v) => { fields.insert(k.clone(), v.clone()); },
+            RequestItem::QueryParam(k, v) => query.push((k.clone(), v.clone())),
+            RequestItem::Header(k, v) => headers.push((k.clone(), v.clone())),
+            RequestItem::FileField(k, path) => files.push((k.clone(), path.clone())),
+        }
+    }
+
+    SplitItems { fields, files, query, headers }
+}
+
+/// a json string/number/bool field, rendered as the plain string form
+/// expected by url-encoded and multipart text parts
+fn field_to_string(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+async fn build_multipart(fields: &serde_json::Map<String, Value>, files: &[(String, PathBuf)]) -> Result<reqwest::multipart::Form> {
+    let mut form = reqwest::multipart::Form::new();
+
+    for (k, v) in fields {
+        form = form.text(k.clone(), field_to_string(v));
+    }
+
+    for (k, path) in files {
+        let bytes = tokio::fs::read(path).await?;
+        let file_name = path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| k.clone());
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        let part = reqwest::multipart::Part::bytes(bytes)
+            .file_name(file_name)
+            .mime_str(mime.as_ref())?;
+        form = form.part(k.clone(), part);
+    }
+
+    Ok(form)
+}
+
+/// layer request-item header overrides onto `defaults` (the client's base
+/// headers plus any session headers). Unlike `ClientBuilder::default_headers`,
+/// which only fills in headers the request left vacant, starting from an
+/// explicit map here means a `name:` unset item actually removes the
+/// default instead of being a no-op.
+fn build_headers(defaults: &header::HeaderMap, items: &[(String, Option<String>)]) -> Result<header::HeaderMap> {
+    let mut headers = defaults.clone();
+    for (k, v) in items {
+        match v {
+            Some(v) => {
+                headers.insert(header::HeaderName::from_str(k)?, v.parse()?);
+            },
+            None => {
+                headers.remove(header::HeaderName::from_str(k)?);
+            },
+        }
+    }
+    Ok(headers)
+}
+
+struct Auth {
+    basic: Option<(String, String)>,
+    bearer: Option<String>,
+}
+
+/// cross-cutting request configuration that doesn't belong on `Request` itself
+struct RequestContext<'a> {
+    auth: &'a Auth,
+    default_headers: &'a header::HeaderMap,
+    session: Option<(&'a str, &'a mut session::Session)>,
+    theme: &'a str,
+    color: bool,
+}
+
+/// send a request, turning reqwest's generic redirect-policy error into a
+/// clearer message when `--max-redirects` was exceeded
+async fn send(builder: reqwest::RequestBuilder) -> Result<Response> {
+    builder.send().await.map_err(|e| {
+        if e.is_redirect() {
+            anyhow!("too many redirects: {}", e)
+        } else {
+            anyhow!(e)
+        }
+    })
+}
+
+async fn request(client: Client, method: Method, args: &Request, mut ctx: RequestContext<'_>) -> Result<()> {
+    let split = split_items(&args.items);
+    let headers = build_headers(ctx.default_headers, &split.headers)?;
+    let has_body_method = method != Method::GET && method != Method::HEAD;
+
+    let mut builder = client.request(method, &args.url)
+        .query(&split.query)
+        .headers(headers);
+
+    // explicit -a/--auth or --bearer overrides any auth saved in the session,
+    // rather than stacking another Authorization header on top of it
+    let auth_override = ctx.auth.basic.is_some() || ctx.auth.bearer.is_some();
+
+    if let Some((_, s)) = &ctx.session {
+        if !auth_override {
+            if let Some((user, pass)) = &s.auth {
+                builder = builder.basic_auth(user, Some(pass.clone()));
+            }
+        }
+        if !s.cookies.is_empty() {
+            // a single `Cookie: a=1; b=2` line, per RFC 6265 — one .header()
+            // call per cookie would instead send several distinct Cookie lines
+            let cookie = s.cookies.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("; ");
+            builder = builder.header(header::COOKIE, cookie);
+        }
+    }
+
+    if let Some((user, pass)) = &ctx.auth.basic {
+        builder = builder.basic_auth(user, Some(pass.clone()));
+    }
+    if let Some(token) = &ctx.auth.bearer {
+        builder = builder.bearer_auth(token);
+    }
+
+    if !split.files.is_empty() && !args.multipart {
+        return Err(anyhow!("file fields (`field@path`) require --multipart"));
+    }
+
+    let stdin_is_body = args.raw
+        || (has_body_method && split.fields.is_empty() && split.files.is_empty() && !atty::is(atty::Stream::Stdin));
+
+    if stdin_is_body {
+        let mut raw_body = Vec::new();
+        std::io::stdin().read_to_end(&mut raw_body)?;
+        let has_content_type = split.headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("content-type"));
+        if !has_content_type {
+            builder = builder.header(header::CONTENT_TYPE, "application/json");
+        }
+        builder = builder.body(raw_body);
+    } else if args.multipart {
+        builder = builder.multipart(build_multipart(&split.fields, &split.files).await?);
+    } else if args.form {
+        if !split.fields.is_empty() {
+            let form: Vec<(&String, String)> = split.fields.iter()
+                .map(|(k, v)| (k, field_to_string(v)))
+                .collect();
+            builder = builder.form(&form);
+        }
+    } else if !split.fields.is_empty() {
+        builder = builder.json(&Value::Object(split.fields));
+    }
+
+    let show_req_headers = args.offline || args.print.contains('H');
+    let show_req_body = args.offline || args.print.contains('B');
+    if show_req_headers || show_req_body {
+        if let Some(preview) = builder.try_clone() {
+            if let Ok(built) = preview.build() {
+                print_request(&built, show_req_headers, show_req_body);
+            }
+        }
+    }
+
+    if args.offline {
+        return Ok(());
+    }
+
+    // header items join the session's saved headers (`name:` drops one);
+    // persist_session writes them out along with auth and cookies
+    if let Some((_, s)) = ctx.session.as_mut() {
+        for (k, v) in &split.headers {
+            match v {
+                Some(v) => { s.headers.insert(k.to_ascii_lowercase(), v.clone()); },
+                None => { s.headers.remove(&k.to_ascii_lowercase()); },
+            }
+        }
+    }
+
+    if args.output.is_some() || args.download {
+        let url: Url = Url::parse(&args.url)?;
+        let (path, cookies) = download::download(builder, &url, args.output.as_deref()).await?;
+        println!("{} {}", "Saved to".green(), path.display());
+        persist_session(ctx.session.take(), ctx.auth, cookies)?;
+        return Ok(());
+    }
+
+    let response = send(builder).await?;
+
+    let cookies = response.cookies().map(|c| (c.name().to_string(), c.value().to_string())).collect::<Vec<_>>();
+    persist_session(ctx.session.take(), ctx.auth, cookies)?;
+
+    print_response(response, ctx.theme, ctx.color, args.print.contains('h'), args.print.contains('b')).await
+}
+
+/// write an explicit `-a/--auth`'s credentials and any fresh cookies back
+/// into the `--session` file, if one is active — shared by both the
+/// streamed-download path and the normal response path
+fn persist_session(session: Option<(&str, &mut session::Session)>, auth: &Auth, cookies: Vec<(String, String)>) -> Result<()> {
+    if let Some((name, s)) = session {
+        if let Some(basic) = &auth.basic {
+            s.auth = Some(basic.clone());
+        }
+        for (k, v) in cookies {
+            s.cookies.insert(k, v);
+        }
+        session::save(name, s)?;
+    }
+    Ok(())
 }
 
 #[tokio::main]
@@ -128,19 +504,47 @@ async fn main() -> Result<()> {
     let opts: Opts = Opts::parse();
     println!("{:?}", opts);
 
+    let mut session = opts.session.as_ref()
+        .map(|name| session::load(name).map(|s| (name.clone(), s)))
+        .transpose()?;
+
     let mut headers = header::HeaderMap::new();
     headers.insert("X-POWERED-BY", "Rust".parse()?);
     headers.insert(header::USER_AGENT, "Rust Httpie".parse()?);
+    if let Some((_, s)) = &session {
+        for (k, v) in &s.headers {
+            headers.insert(header::HeaderName::from_str(k)?, v.parse()?);
+        }
+    }
 
-    let client = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()?;
+    let mut client_builder = reqwest::Client::builder()
+        .cookie_store(session.is_some())
+        .danger_accept_invalid_certs(opts.verify == "no")
+        .redirect(match opts.max_redirects {
+            Some(n) => reqwest::redirect::Policy::limited(n),
+            None => reqwest::redirect::Policy::default(),
+        });
+    if let Some(proxy) = &opts.proxy {
+        client_builder = client_builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    if let Some(secs) = opts.timeout {
+        client_builder = client_builder.timeout(std::time::Duration::from_secs(secs));
+    }
+    let client = client_builder.build()?;
 
-    let result = match opts.sub_cmd {
-        SubCommand::Get(ref args) => get(client, args).await?,
-        SubCommand::Post(ref args) => post(client, args).await?,
+    let auth = Auth { basic: opts.auth.clone(), bearer: opts.bearer.clone() };
+    let color = !opts.no_color && atty::is(atty::Stream::Stdout);
+    let method = opts.sub_cmd.method();
+    let args = opts.sub_cmd.args();
+    let session_ref = session.as_mut().map(|(name, s)| (name.as_str(), s));
+    let ctx = RequestContext {
+        auth: &auth,
+        default_headers: &headers,
+        session: session_ref,
+        theme: &opts.theme,
+        color,
     };
-    Ok(result)
+    request(client, method, args, ctx).await
 }
 
 #[cfg(test)]
@@ -155,21 +559,103 @@ mod tests {
     }
 
     #[test]
-    fn parse_kv_pair_works() {
-        assert!(parse_kv_pair("a").is_err());
+    fn parse_request_item_works() {
+        assert!(parse_request_item("a").is_err());
         assert_eq!(
-            parse_kv_pair("a=1").unwrap(),
-            KvPair {
-                k: "a".into(),
-                v: "1".into(),
-            }
+            parse_request_item("a=1").unwrap(),
+            RequestItem::JsonField("a".into(), Value::String("1".into())),
         );
         assert_eq!(
-            parse_kv_pair("b=").unwrap(),
-            KvPair {
-                k: "b".into(),
-                v: "".into(),
-            }
+            parse_request_item("n:=1").unwrap(),
+            RequestItem::RawJsonField("n".into(), Value::from(1)),
+        );
+        assert_eq!(
+            parse_request_item("arr:=[1,2]").unwrap(),
+            RequestItem::RawJsonField("arr".into(), Value::from(vec![1, 2])),
+        );
+        assert_eq!(
+            parse_request_item("page==2").unwrap(),
+            RequestItem::QueryParam("page".into(), "2".into()),
+        );
+        assert_eq!(
+            parse_request_item("X-Token:abc").unwrap(),
+            RequestItem::Header("X-Token".into(), Some("abc".into())),
+        );
+        assert_eq!(
+            parse_request_item("X-Token:").unwrap(),
+            RequestItem::Header("X-Token".into(), None),
+        );
+        assert_eq!(
+            parse_request_item("avatar@photo.png").unwrap(),
+            RequestItem::FileField("avatar".into(), PathBuf::from("photo.png")),
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parse_request_item_picks_leftmost_separator() {
+        // the `@` occurs before the path's `:`, so this is a file field, not a header
+        assert_eq!(
+            parse_request_item("avatar@C:\\photo.png").unwrap(),
+            RequestItem::FileField("avatar".into(), PathBuf::from("C:\\photo.png")),
+        );
+    }
+
+    #[test]
+    fn build_headers_unset_removes_a_default() {
+        let mut defaults = header::HeaderMap::new();
+        defaults.insert("x-powered-by", "Rust".parse().unwrap());
+
+        let headers = build_headers(&defaults, &[("x-powered-by".into(), None)]).unwrap();
+
+        assert!(!headers.contains_key("x-powered-by"));
+    }
+
+    #[test]
+    fn built_request_carries_default_headers_for_offline_preview() {
+        // --offline/--print=H build the request via RequestBuilder::build()
+        // without sending it; since defaults are merged in build_headers
+        // (not ClientBuilder::default_headers), they must show up here too
+        let mut defaults = header::HeaderMap::new();
+        defaults.insert("x-powered-by", "Rust".parse().unwrap());
+
+        let headers = build_headers(&defaults, &[]).unwrap();
+        let built = reqwest::Client::new()
+            .request(Method::GET, "https://example.com")
+            .headers(headers)
+            .build()
+            .unwrap();
+
+        assert_eq!(built.headers().get("x-powered-by").unwrap(), "Rust");
+    }
+
+    #[test]
+    fn split_items_separates_fields_files_query_and_headers() {
+        let items = vec![
+            RequestItem::JsonField("name".into(), Value::String("bob".into())),
+            RequestItem::FileField("avatar".into(), PathBuf::from("photo.png")),
+            RequestItem::QueryParam("page".into(), "2".into()),
+            RequestItem::Header("X-Token".into(), Some("abc".into())),
+        ];
+
+        let split = split_items(&items);
+
+        assert_eq!(split.fields.get("name"), Some(&Value::String("bob".into())));
+        assert_eq!(split.files, vec![("avatar".to_string(), PathBuf::from("photo.png"))]);
+        assert_eq!(split.query, vec![("page".to_string(), "2".to_string())]);
+        assert_eq!(split.headers, vec![("X-Token".to_string(), Some("abc".to_string()))]);
+    }
+
+    #[test]
+    fn field_to_string_unwraps_json_strings() {
+        assert_eq!(field_to_string(&Value::String("bob".into())), "bob");
+        assert_eq!(field_to_string(&Value::from(3)), "3");
+        assert_eq!(field_to_string(&Value::Bool(true)), "true");
+    }
+
+    #[test]
+    fn parse_auth_splits_on_first_colon() {
+        assert_eq!(parse_auth("user:pass").unwrap(), ("user".into(), "pass".into()));
+        assert_eq!(parse_auth("user:pass:word").unwrap(), ("user".into(), "pass:word".into()));
+        assert!(parse_auth("no-colon").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// default headers, basic-auth credentials and cookies persisted between
+/// invocations under `~/.config/httpie/sessions/<name>.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub auth: Option<(String, String)>,
+    #[serde(default)]
+    pub cookies: HashMap<String, String>,
+}
+
+fn path_for(name: &str) -> Result<PathBuf> {
+    let mut dir = dirs::config_dir().ok_or_else(|| anyhow!("could not determine config directory"))?;
+    dir.push("httpie/sessions");
+    std::fs::create_dir_all(&dir)?;
+    dir.push(format!("{}.json", name));
+    Ok(dir)
+}
+
+/// load a session by name, or a fresh empty one if it hasn't been saved yet
+pub fn load(name: &str) -> Result<Session> {
+    let path = path_for(name)?;
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)?),
+        Err(_) => Ok(Session::default()),
+    }
+}
+
+pub fn save(name: &str, session: &Session) -> Result<()> {
+    let path = path_for(name)?;
+    std::fs::write(path, serde_json::to_string_pretty(session)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_round_trips_through_json() {
+        let mut session = Session::default();
+        session.headers.insert("x-api-key".into(), "secret".into());
+        session.auth = Some(("user".into(), "pass".into()));
+        session.cookies.insert("a".into(), "1".into());
+
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: Session = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.headers.get("x-api-key"), Some(&"secret".to_string()));
+        assert_eq!(restored.auth, Some(("user".into(), "pass".into())));
+        assert_eq!(restored.cookies.get("a"), Some(&"1".to_string()));
+    }
+
+    #[test]
+    fn missing_fields_default_to_empty() {
+        let session: Session = serde_json::from_str("{}").unwrap();
+        assert!(session.headers.is_empty());
+        assert!(session.auth.is_none());
+        assert!(session.cookies.is_empty());
+    }
+}